@@ -2,7 +2,6 @@ extern crate proc_macro;
 use crate::proc_macro::TokenStream;
 use proc_macro2::{Ident, Span};
 use quote::{quote, ToTokens};
-use syn;
 
 /// A struct that contains the name of a struct field and the corresponding type
 #[derive(Debug)]
@@ -0,0 +1,105 @@
+//! Diagnostic dumps for panics caught at the FFI boundary.
+//!
+//! Once a crash dump directory is configured, every panic caught by `catch_panic_response` writes
+//! a small text file with the panic message, the panicking thread and the backtrace of the panic
+//! site. The path of that file is appended to the error message returned to the caller, so that
+//! bug reports from downstream users point to something actionable.
+
+use std::backtrace::Backtrace;
+use std::cell::RefCell;
+use std::fs;
+use std::io;
+use std::panic;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, Once};
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+static CRASH_DUMP_DIR: Mutex<Option<PathBuf>> = Mutex::new(None);
+static INSTALL_PANIC_HOOK: Once = Once::new();
+static DUMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    // The backtrace of the most recent panic on this thread, recorded by the panic hook as the
+    // stack is already unwound once the panic is caught.
+    static LAST_PANIC_BACKTRACE: RefCell<Option<Backtrace>> = const { RefCell::new(None) };
+}
+
+/// Set (or unset with `None`) the directory crash dumps are written to
+///
+/// Setting a directory for the first time installs a panic hook which, while a directory is set,
+/// records the backtrace of every panic before calling the previously installed hook.
+pub fn set_crash_dump_dir(dir: Option<PathBuf>) {
+    if dir.is_some() {
+        INSTALL_PANIC_HOOK.call_once(|| {
+            let previous_hook = panic::take_hook();
+            panic::set_hook(Box::new(move |info| {
+                if get_crash_dump_dir().is_some() {
+                    LAST_PANIC_BACKTRACE.with(|backtrace| {
+                        *backtrace.borrow_mut() = Some(Backtrace::force_capture());
+                    });
+                }
+                previous_hook(info);
+            }));
+        });
+    }
+    *CRASH_DUMP_DIR.lock().unwrap_or_else(|e| e.into_inner()) = dir;
+}
+
+/// Returns the currently configured crash dump directory
+pub fn get_crash_dump_dir() -> Option<PathBuf> {
+    CRASH_DUMP_DIR
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+}
+
+// Forget the backtrace of an earlier panic on this thread. Panics caught elsewhere, or payloads
+// passed on with `panic::resume_unwind()` (which doesn't run the hook), would otherwise end up
+// with an unrelated backtrace in their dump.
+pub(crate) fn clear_panic_backtrace() {
+    LAST_PANIC_BACKTRACE.with(|backtrace| backtrace.borrow_mut().take());
+}
+
+/// Write a crash dump for a panic with the given message into the crash dump directory
+///
+/// Returns `Ok(None)` if no crash dump directory is configured. Worker threads that catch panics
+/// themselves can use this to produce the same dumps as `catch_panic_response`.
+pub fn write_crash_dump(panic_message: &str) -> io::Result<Option<PathBuf>> {
+    let dir = match get_crash_dump_dir() {
+        Some(dir) => dir,
+        None => return Ok(None),
+    };
+    let backtrace = LAST_PANIC_BACKTRACE.with(|backtrace| backtrace.borrow_mut().take());
+    let path = dump_path(&dir);
+
+    let contents = format!(
+        "message: {}\nthread: {}\nprocess: {}\n\nbacktrace:\n{}\n",
+        panic_message,
+        thread::current().name().unwrap_or("<unnamed>"),
+        process::id(),
+        match backtrace {
+            Some(backtrace) => backtrace.to_string(),
+            None => "<not captured>".to_string(),
+        }
+    );
+    fs::create_dir_all(&dir)?;
+    fs::write(&path, contents)?;
+    Ok(Some(path))
+}
+
+// a unique file name, even for several panics within the same second
+fn dump_path(dir: &Path) -> PathBuf {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0);
+    dir.join(format!(
+        "rust-panic-{}-{}-{}.txt",
+        timestamp,
+        process::id(),
+        DUMP_COUNTER.fetch_add(1, Ordering::SeqCst)
+    ))
+}
@@ -0,0 +1,44 @@
+//! Consistent classification of `io::Error`s into response status codes.
+//!
+//! Downstream retry logic branches on the status code, hence every operation needs to classify
+//! the same failure the same way. The policy is:
+//!
+//! - `FCPCallerError`: the request can't succeed as made, retrying with the same arguments is
+//!   pointless. This covers paths that don't exist, can't be accessed or are malformed, things that
+//!   already exist and invalid arguments (`NotFound`, `PermissionDenied`, `NotADirectory`,
+//!   `IsADirectory`, `InvalidFilename`, `AlreadyExists`, `InvalidInput`).
+//! - `FCPReceiverError`: everything else. The request was fine, but the receiving side failed
+//!   while carrying it out (e.g. `InvalidData`, `UnexpectedEof`, `WriteZero`, `Interrupted`,
+//!   `TimedOut`, `Other`).
+
+use std::io;
+
+use crate::{rust_str_to_c_str, FCPResponseStatus};
+
+/// Returns the status code for an `io::ErrorKind`
+pub fn io_error_kind_status(kind: io::ErrorKind) -> FCPResponseStatus {
+    match kind {
+        io::ErrorKind::NotFound
+        | io::ErrorKind::PermissionDenied
+        | io::ErrorKind::NotADirectory
+        | io::ErrorKind::IsADirectory
+        | io::ErrorKind::InvalidFilename
+        | io::ErrorKind::AlreadyExists
+        | io::ErrorKind::InvalidInput => FCPResponseStatus::FCPCallerError,
+        _ => FCPResponseStatus::FCPReceiverError,
+    }
+}
+
+/// Returns the status code for an `io::Error`
+pub fn io_error_status(err: &io::Error) -> FCPResponseStatus {
+    io_error_kind_status(err.kind())
+}
+
+/// Returns the status code and error message for an `io::Error`
+///
+/// The result can directly be passed into `CodeAndMessage::set_error()`. NUL bytes in the message
+/// are escaped as `\0`, as they can't be part of a C string.
+pub fn io_error_code_and_message(err: &io::Error) -> (FCPResponseStatus, *const libc::c_char) {
+    let message = format!("{}", err).replace('\0', "\\0");
+    (io_error_status(err), rust_str_to_c_str(message))
+}
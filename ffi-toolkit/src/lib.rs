@@ -1,7 +1,24 @@
+use std::any::{self, Any};
 use std::borrow::Cow;
 use std::ffi::{CStr, CString};
 use std::panic;
 use std::path::PathBuf;
+use std::ptr;
+use std::slice;
+use std::str;
+
+mod crash_dump;
+mod io_error;
+mod live_objects;
+
+use crate::crash_dump::clear_panic_backtrace;
+pub use crate::crash_dump::{get_crash_dump_dir, set_crash_dump_dir, write_crash_dump};
+pub use crate::io_error::{io_error_code_and_message, io_error_kind_status, io_error_status};
+pub use crate::live_objects::{
+    ffi_toolkit_destroy_live_object_counts_response, ffi_toolkit_get_live_object_counts,
+    get_live_ffi_object_counts, FFILiveObjectCount, LiveObjectCountsResponse, C_STR_TYPE_NAME,
+};
+use crate::live_objects::{track_alloc, track_free};
 
 #[repr(C)]
 #[derive(PartialEq, Debug, Copy, Clone)]
@@ -37,22 +54,60 @@ macro_rules! code_and_message_impl {
 
 // produce a C string from a Rust string
 pub fn rust_str_to_c_str<T: Into<String>>(s: T) -> *mut libc::c_char {
-    CString::new(s.into()).unwrap().into_raw()
+    let c_str = CString::new(s.into()).unwrap().into_raw();
+    track_alloc(c_str, C_STR_TYPE_NAME);
+    c_str
 }
 
-// consume a C string-pointer and free its memory
+/// Consume a C string-pointer and free its memory
+///
+/// # Safety
+///
+/// `ptr` must be NULL or a pointer returned by `CString::into_raw()` (e.g. through
+/// `rust_str_to_c_str()`) that hasn't been freed yet.
 pub unsafe fn free_c_str(ptr: *mut libc::c_char) {
     if !ptr.is_null() {
+        track_free(ptr);
         let _ = CString::from_raw(ptr);
     }
 }
 
-// return a forgotten raw pointer to something of type T
+/// Return a forgotten raw pointer to something of type T
+///
+/// The object is not counted by `get_live_ffi_object_counts()`, use `raw_ptr_tracked()` for that.
 pub fn raw_ptr<T>(thing: T) -> *mut T {
     Box::into_raw(Box::new(thing))
 }
 
-// transmutes a C string to a copy-on-write Rust string
+/// Return a forgotten raw pointer to something of type T, tracked by
+/// `get_live_ffi_object_counts()`
+///
+/// In debug builds the object is counted until it's freed with `free_raw_ptr()`. Freeing it any
+/// other way (e.g. with `Box::from_raw()`) makes it show up as leaked.
+pub fn raw_ptr_tracked<T>(thing: T) -> *mut T {
+    let ptr = Box::into_raw(Box::new(thing));
+    track_alloc(ptr, any::type_name::<T>());
+    ptr
+}
+
+/// Consume a raw pointer returned by `raw_ptr` or `raw_ptr_tracked` and drop what it points to
+///
+/// # Safety
+///
+/// `ptr` must be NULL or a pointer returned by `raw_ptr()` or `raw_ptr_tracked()` that hasn't been
+/// freed yet.
+pub unsafe fn free_raw_ptr<T>(ptr: *mut T) {
+    if !ptr.is_null() {
+        track_free(ptr);
+        let _ = Box::from_raw(ptr);
+    }
+}
+
+/// Transmutes a C string to a copy-on-write Rust string
+///
+/// # Safety
+///
+/// `x` must be NULL or point to a NUL-terminated string that stays valid for `'a`.
 pub unsafe fn c_str_to_rust_str<'a>(x: *const libc::c_char) -> Cow<'a, str> {
     if x.is_null() {
         Cow::from("")
@@ -61,18 +116,89 @@ pub unsafe fn c_str_to_rust_str<'a>(x: *const libc::c_char) -> Cow<'a, str> {
     }
 }
 
-// cast from mutable to constant reference
+/// Cast from mutable to constant reference
+///
+/// # Safety
+///
+/// `x` must point to a valid `T` that stays valid, and isn't mutated, for `'a`.
 pub unsafe fn cast_const<'a, T>(x: *mut T) -> &'a T {
     assert!(!x.is_null(), "Object argument was null");
-    (&(*x))
+    &(*x)
 }
 
-// transmutes a C string to a PathBuf
+/// Transmutes a C string to a PathBuf
+///
+/// # Safety
+///
+/// `x` must be NULL or point to a valid NUL-terminated string.
 pub unsafe fn c_str_to_pbuf(x: *const libc::c_char) -> PathBuf {
     PathBuf::from(String::from(c_str_to_rust_str(x)))
 }
 
+/// Transmutes a UTF-8 byte buffer (pointer + length, no NUL terminator) to a copy-on-write Rust
+/// string
+///
+/// Invalid UTF-8 is replaced with U+FFFD and a NULL `ptr` results in an empty string, no matter
+/// what `len` is. Use `try_c_bytes_to_rust_str()` if those should be reported as caller errors.
+///
+/// # Safety
+///
+/// Unless `ptr` is NULL or `len` is zero, `ptr` must be valid for reads of `len` bytes for the
+/// caller-chosen lifetime `'a`.
+pub unsafe fn c_bytes_to_rust_str<'a>(ptr: *const u8, len: libc::size_t) -> Cow<'a, str> {
+    if ptr.is_null() || len == 0 {
+        Cow::from("")
+    } else {
+        String::from_utf8_lossy(slice::from_raw_parts(ptr, len))
+    }
+}
+
+/// Transmutes a UTF-8 byte buffer (pointer + length, no NUL terminator) to a PathBuf
+///
+/// Lossy in the same way as `c_bytes_to_rust_str()`. For path arguments that means a different
+/// path (or the current directory) might be used, so prefer `try_c_bytes_to_pbuf()`.
+///
+/// # Safety
+///
+/// Unless `ptr` is NULL or `len` is zero, `ptr` must be valid for reads of `len` bytes.
+pub unsafe fn c_bytes_to_pbuf(ptr: *const u8, len: libc::size_t) -> PathBuf {
+    PathBuf::from(String::from(c_bytes_to_rust_str(ptr, len)))
+}
+
+/// Transmutes a UTF-8 byte buffer (pointer + length, no NUL terminator) to a Rust string
+///
+/// Returns `None` if `ptr` is NULL while `len` isn't zero, or if the bytes aren't valid UTF-8, so
+/// that the caller can respond with `FCPCallerError`.
+///
+/// # Safety
+///
+/// Unless `ptr` is NULL or `len` is zero, `ptr` must be valid for reads of `len` bytes for the
+/// caller-chosen lifetime `'a`.
+pub unsafe fn try_c_bytes_to_rust_str<'a>(ptr: *const u8, len: libc::size_t) -> Option<&'a str> {
+    if len == 0 {
+        Some("")
+    } else if ptr.is_null() {
+        None
+    } else {
+        str::from_utf8(slice::from_raw_parts(ptr, len)).ok()
+    }
+}
+
+/// Transmutes a UTF-8 byte buffer (pointer + length, no NUL terminator) to a PathBuf
+///
+/// Returns `None` under the same conditions as `try_c_bytes_to_rust_str()`.
+///
+/// # Safety
+///
+/// Unless `ptr` is NULL or `len` is zero, `ptr` must be valid for reads of `len` bytes.
+pub unsafe fn try_c_bytes_to_pbuf(ptr: *const u8, len: libc::size_t) -> Option<PathBuf> {
+    try_c_bytes_to_rust_str(ptr, len).map(PathBuf::from)
+}
+
 ///// Catch panics and return an error response
+//
+// The error response returned on panic is allocated with `raw_ptr_tracked()`, hence destroy it
+// with `free_raw_ptr()` to keep `get_live_ffi_object_counts()` accurate.
 pub fn catch_panic_response<F, T>(callback: F) -> *mut T
 where
     T: Default + CodeAndMessage,
@@ -80,20 +206,80 @@ where
 {
     // Using AssertUnwindSafe is code smell. Though catching our panics here is really
     // last resort, so it should be OK.
+    clear_panic_backtrace();
     let maybe_panic = panic::catch_unwind(panic::AssertUnwindSafe(callback));
     match maybe_panic {
         Ok(return_value) => return_value,
         Err(panic) => {
-            let error_msg = match panic.downcast_ref::<&'static str>() {
-                Some(message) => message,
-                _ => "no unwind information",
-            };
             let mut response = T::default();
-            let message = CString::new(format!("Rust panic: {}", error_msg))
-                .unwrap()
-                .into_raw();
+            let message = rust_str_to_c_str(panic_message(panic));
             response.set_error((FCPResponseStatus::FCPUnclassifiedError, message));
-            raw_ptr(response)
+            raw_ptr_tracked(response)
         }
     }
 }
+
+/// Catch panics and return the status code directly
+///
+/// This is the counterpart of `catch_panic_response()` for entry points that return their status
+/// code and hand back results through out-pointers instead of allocating a response.
+/// `error_msg_out` (unless it is NULL) is set to NULL before the callback runs. On panic the error
+/// message is written to it; the caller owns it and needs to free it with `free_c_str()`.
+///
+/// A callback that fails without panicking reports its message the same way, by writing a string
+/// created with `rust_str_to_c_str()` to `error_msg_out` before returning its error status.
+///
+/// # Safety
+///
+/// `error_msg_out` must be NULL or valid for writing a pointer.
+pub unsafe fn catch_panic_status<F>(
+    error_msg_out: *mut *const libc::c_char,
+    callback: F,
+) -> FCPResponseStatus
+where
+    F: FnOnce() -> FCPResponseStatus,
+{
+    if !error_msg_out.is_null() {
+        *error_msg_out = ptr::null();
+    }
+    clear_panic_backtrace();
+    let maybe_panic = panic::catch_unwind(panic::AssertUnwindSafe(callback));
+    match maybe_panic {
+        Ok(status) => status,
+        Err(panic) => {
+            // Always build the message, so that a crash dump is written even if the caller
+            // isn't interested in the message
+            let message = rust_str_to_c_str(panic_message(panic));
+            if error_msg_out.is_null() {
+                free_c_str(message);
+            } else {
+                *error_msg_out = message;
+            }
+            FCPResponseStatus::FCPUnclassifiedError
+        }
+    }
+}
+
+// the error message for a caught panic, including the crash dump path if one was written
+fn panic_message(panic: Box<dyn Any + Send>) -> String {
+    // `panic!("literal")` carries a `&'static str`, formatted panics (including `unwrap()` and
+    // `expect()`) a `String`
+    let error_msg = match panic.downcast_ref::<&'static str>() {
+        Some(message) => message,
+        _ => match panic.downcast_ref::<String>() {
+            Some(message) => message.as_str(),
+            _ => "no unwind information",
+        },
+    };
+    let message = match write_crash_dump(error_msg) {
+        Ok(Some(path)) => format!("Rust panic: {} (crash dump: {})", error_msg, path.display()),
+        Ok(None) => format!("Rust panic: {}", error_msg),
+        Err(err) => format!(
+            "Rust panic: {} (failed to write crash dump: {})",
+            error_msg, err
+        ),
+    };
+    // The message becomes a C string, an interior NUL byte would make that panic outside of
+    // `catch_unwind()`
+    message.replace('\0', "\\0")
+}
@@ -0,0 +1,116 @@
+//! Debug-build bookkeeping of objects handed across the FFI boundary.
+//!
+//! Every C string produced by `rust_str_to_c_str` and every object produced by `raw_ptr_tracked`
+//! is recorded by its address until it is released again through `free_c_str` or `free_raw_ptr`.
+//! Binding authors can call `get_live_ffi_object_counts()` at the end of a test run to find
+//! forgotten `destroy_*` calls. Freeing a pointer that was never recorded doesn't touch the
+//! counts. In release builds nothing is tracked and the counts are always empty.
+//!
+//! Bindings in other languages can query the counts through
+//! `ffi_toolkit_get_live_object_counts()`.
+
+use std::collections::BTreeMap;
+use std::ffi::CString;
+#[cfg(debug_assertions)]
+use std::sync::Mutex;
+
+use drop_struct_macro_derive::DropStructMacro;
+
+// `free_c_str` is needed by `DropStructMacro`
+use crate::{free_c_str, free_raw_ptr, raw_ptr};
+
+/// The name C strings are counted under
+pub const C_STR_TYPE_NAME: &str = "c_str";
+
+// address -> type name of every live tracked object
+#[cfg(debug_assertions)]
+static LIVE_OBJECTS: Mutex<BTreeMap<usize, &'static str>> = Mutex::new(BTreeMap::new());
+
+#[cfg(debug_assertions)]
+pub(crate) fn track_alloc<T>(ptr: *const T, type_name: &'static str) {
+    let mut live_objects = LIVE_OBJECTS.lock().unwrap_or_else(|e| e.into_inner());
+    live_objects.insert(ptr as *const () as usize, type_name);
+}
+
+#[cfg(not(debug_assertions))]
+pub(crate) fn track_alloc<T>(_ptr: *const T, _type_name: &'static str) {}
+
+#[cfg(debug_assertions)]
+pub(crate) fn track_free<T>(ptr: *const T) {
+    let mut live_objects = LIVE_OBJECTS.lock().unwrap_or_else(|e| e.into_inner());
+    live_objects.remove(&(ptr as *const () as usize));
+}
+
+#[cfg(not(debug_assertions))]
+pub(crate) fn track_free<T>(_ptr: *const T) {}
+
+/// Returns the number of live FFI objects by type name
+///
+/// C strings are counted under `C_STR_TYPE_NAME`, all other objects under
+/// `std::any::type_name()` of their type. Type names are best-effort and not guaranteed to be
+/// unique. Types without any live objects are omitted. Always empty in release builds.
+pub fn get_live_ffi_object_counts() -> BTreeMap<&'static str, usize> {
+    #[cfg(debug_assertions)]
+    {
+        let live_objects = LIVE_OBJECTS.lock().unwrap_or_else(|e| e.into_inner());
+        let mut counts = BTreeMap::new();
+        for type_name in live_objects.values() {
+            *counts.entry(*type_name).or_insert(0) += 1;
+        }
+        counts
+    }
+    #[cfg(not(debug_assertions))]
+    {
+        BTreeMap::new()
+    }
+}
+
+/// The number of live FFI objects of a single type
+#[repr(C)]
+#[derive(DropStructMacro)]
+pub struct FFILiveObjectCount {
+    pub type_name: *const libc::c_char,
+    pub count: libc::size_t,
+}
+
+#[repr(C)]
+#[derive(DropStructMacro)]
+pub struct LiveObjectCountsResponse {
+    pub counts_len: libc::size_t,
+    pub counts_ptr: *const FFILiveObjectCount,
+}
+
+/// Returns the number of live FFI objects by type name, callable from C
+///
+/// This is the FFI-safe form of `get_live_ffi_object_counts()`. The response itself isn't
+/// tracked, it needs to be freed with `ffi_toolkit_destroy_live_object_counts_response()`.
+#[no_mangle]
+pub extern "C" fn ffi_toolkit_get_live_object_counts() -> *mut LiveObjectCountsResponse {
+    let counts = get_live_ffi_object_counts()
+        .into_iter()
+        .map(|(type_name, count)| FFILiveObjectCount {
+            // Not created with `rust_str_to_c_str()` so that querying doesn't change the counts
+            type_name: CString::new(type_name).unwrap_or_default().into_raw(),
+            count,
+        })
+        .collect::<Vec<_>>()
+        .into_boxed_slice();
+    let counts_len = counts.len();
+    raw_ptr(LiveObjectCountsResponse {
+        counts_len,
+        counts_ptr: Box::into_raw(counts) as *const FFILiveObjectCount,
+    })
+}
+
+/// Frees a response returned by `ffi_toolkit_get_live_object_counts()`
+///
+/// # Safety
+///
+/// `ptr` must be NULL or a pointer returned by `ffi_toolkit_get_live_object_counts()` that hasn't
+/// been freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn ffi_toolkit_destroy_live_object_counts_response(
+    ptr: *mut LiveObjectCountsResponse,
+) {
+    free_raw_ptr(ptr);
+}
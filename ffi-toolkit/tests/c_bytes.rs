@@ -0,0 +1,73 @@
+use std::path::PathBuf;
+use std::ptr;
+
+use ffi_toolkit::{
+    c_bytes_to_pbuf, c_bytes_to_rust_str, try_c_bytes_to_pbuf, try_c_bytes_to_rust_str,
+};
+
+#[test]
+fn c_bytes_to_rust_str_reads_exactly_len_bytes() {
+    // No NUL terminator, and trailing bytes past `len` must be ignored.
+    let bytes = b"/var/tmp/staging-and-more";
+    unsafe {
+        assert_eq!(c_bytes_to_rust_str(bytes.as_ptr(), 16), "/var/tmp/staging");
+        assert_eq!(
+            c_bytes_to_pbuf(bytes.as_ptr(), 16),
+            PathBuf::from("/var/tmp/staging")
+        );
+    }
+}
+
+#[test]
+fn c_bytes_to_rust_str_null_or_empty() {
+    unsafe {
+        assert_eq!(c_bytes_to_rust_str(ptr::null(), 10), "");
+        assert_eq!(c_bytes_to_rust_str(b"abc".as_ptr(), 0), "");
+        assert_eq!(c_bytes_to_pbuf(ptr::null(), 0), PathBuf::from(""));
+    }
+}
+
+#[test]
+fn c_bytes_to_rust_str_invalid_utf8_is_lossy() {
+    let bytes = [b'a', 0xff, b'b'];
+    unsafe {
+        assert_eq!(
+            c_bytes_to_rust_str(bytes.as_ptr(), bytes.len()),
+            "a\u{fffd}b"
+        );
+    }
+}
+
+#[test]
+fn try_c_bytes_to_rust_str_reads_exactly_len_bytes() {
+    let bytes = b"/var/tmp/staging-and-more";
+    unsafe {
+        assert_eq!(
+            try_c_bytes_to_rust_str(bytes.as_ptr(), 16),
+            Some("/var/tmp/staging")
+        );
+        assert_eq!(
+            try_c_bytes_to_pbuf(bytes.as_ptr(), 16),
+            Some(PathBuf::from("/var/tmp/staging"))
+        );
+    }
+}
+
+/// A NULL pointer is only fine for an empty string, otherwise it's a caller error.
+#[test]
+fn try_c_bytes_to_rust_str_null() {
+    unsafe {
+        assert_eq!(try_c_bytes_to_rust_str(ptr::null(), 0), Some(""));
+        assert_eq!(try_c_bytes_to_rust_str(ptr::null(), 10), None);
+        assert_eq!(try_c_bytes_to_pbuf(ptr::null(), 10), None);
+    }
+}
+
+#[test]
+fn try_c_bytes_to_rust_str_invalid_utf8() {
+    let bytes = [b'a', 0xff, b'b'];
+    unsafe {
+        assert_eq!(try_c_bytes_to_rust_str(bytes.as_ptr(), bytes.len()), None);
+        assert_eq!(try_c_bytes_to_pbuf(bytes.as_ptr(), bytes.len()), None);
+    }
+}
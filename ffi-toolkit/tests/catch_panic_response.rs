@@ -1,44 +1,26 @@
 use std::ffi::CString;
 use std::ptr;
 
-use drop_struct_macro_derive::DropStructMacro;
-// `free_c_str` is needed by `DropStructMacro`
-// `CodeAndMessage` is the trait implemented by `code_and_message_impl`
-use ffi_toolkit::{
-    catch_panic_response, code_and_message_impl, free_c_str, raw_ptr, CodeAndMessage,
-    FCPResponseStatus,
-};
+use ffi_toolkit::{catch_panic_response, raw_ptr, FCPResponseStatus};
 
-#[repr(C)]
-#[derive(DropStructMacro)]
-pub struct BasicResponse {
-    pub status_code: FCPResponseStatus,
-    pub error_msg: *const libc::c_char,
-    pub is_valid: bool,
-}
-
-impl Default for BasicResponse {
-    fn default() -> Self {
-        BasicResponse {
-            status_code: FCPResponseStatus::FCPNoError,
-            error_msg: ptr::null(),
-            is_valid: false,
-        }
-    }
-}
+mod common;
 
-code_and_message_impl!(BasicResponse);
+use common::BasicResponse;
 
 unsafe extern "C" fn fn_does_not_panic() -> *mut BasicResponse {
-    let mut response = BasicResponse::default();
-    response.is_valid = true;
+    let response = BasicResponse {
+        is_valid: true,
+        ..Default::default()
+    };
     raw_ptr(response)
 }
 
 unsafe extern "C" fn fn_does_not_panic_with_catch_panic() -> *mut BasicResponse {
     catch_panic_response(|| {
-        let mut response = BasicResponse::default();
-        response.is_valid = true;
+        let response = BasicResponse {
+            is_valid: true,
+            ..Default::default()
+        };
         raw_ptr(response)
     })
 }
@@ -47,6 +29,14 @@ unsafe extern "C" fn fn_does_panic_with_catch_panic() -> *mut BasicResponse {
     catch_panic_response(|| panic!("I do panic"))
 }
 
+unsafe extern "C" fn fn_does_panic_formatted_with_catch_panic() -> *mut BasicResponse {
+    catch_panic_response(|| panic!("I do panic with {}", "a formatted message"))
+}
+
+unsafe extern "C" fn fn_does_panic_with_nul_with_catch_panic() -> *mut BasicResponse {
+    catch_panic_response(|| panic!("I do panic with a {} byte", '\0'))
+}
+
 /// Nothing special in this test, this is just there to make sure things work the same with
 /// or without a `catch_panic()` closure.
 #[test]
@@ -89,3 +79,30 @@ fn does_panic_with_catch_panic_response() {
         assert_eq!(error_message, "Rust panic: I do panic");
     }
 }
+
+/// Formatted panics (like the ones from `unwrap()`) carry a `String` instead of a `&str`.
+#[test]
+fn does_panic_formatted_with_catch_panic_response() {
+    unsafe {
+        let response = fn_does_panic_formatted_with_catch_panic();
+        let error_message = CString::from_raw((*response).error_msg as *mut _)
+            .into_string()
+            .unwrap();
+        assert_eq!(
+            error_message,
+            "Rust panic: I do panic with a formatted message"
+        );
+    }
+}
+
+/// NUL bytes in the panic message are escaped, as they can't be part of a C string.
+#[test]
+fn does_panic_with_nul_with_catch_panic_response() {
+    unsafe {
+        let response = fn_does_panic_with_nul_with_catch_panic();
+        let error_message = CString::from_raw((*response).error_msg as *mut _)
+            .into_string()
+            .unwrap();
+        assert_eq!(error_message, "Rust panic: I do panic with a \\0 byte");
+    }
+}
@@ -0,0 +1,73 @@
+use std::ffi::CString;
+use std::ptr;
+
+use ffi_toolkit::{catch_panic_status, rust_str_to_c_str, FCPResponseStatus};
+
+unsafe extern "C" fn fn_does_not_panic_with_catch_panic(
+    error_msg_out: *mut *const libc::c_char,
+    num_bytes_out: *mut u64,
+) -> FCPResponseStatus {
+    catch_panic_status(error_msg_out, || {
+        *num_bytes_out = 42;
+        FCPResponseStatus::FCPNoError
+    })
+}
+
+unsafe extern "C" fn fn_does_fail_with_catch_panic(
+    error_msg_out: *mut *const libc::c_char,
+) -> FCPResponseStatus {
+    catch_panic_status(error_msg_out, || {
+        *error_msg_out = rust_str_to_c_str("I do fail");
+        FCPResponseStatus::FCPCallerError
+    })
+}
+
+unsafe extern "C" fn fn_does_panic_with_catch_panic(
+    error_msg_out: *mut *const libc::c_char,
+) -> FCPResponseStatus {
+    catch_panic_status(error_msg_out, || panic!("I do panic"))
+}
+
+#[test]
+fn does_not_panic_with_catch_panic_status() {
+    // The error message is reset even if the caller didn't initialise it
+    let mut error_msg = ptr::NonNull::dangling().as_ptr() as *const libc::c_char;
+    let mut num_bytes = 0;
+    unsafe {
+        let status = fn_does_not_panic_with_catch_panic(&mut error_msg, &mut num_bytes);
+        assert_eq!(status, FCPResponseStatus::FCPNoError);
+    }
+    assert_eq!(num_bytes, 42);
+    assert_eq!(error_msg, ptr::null());
+}
+
+/// Status codes and error messages set by the callback are passed through untouched.
+#[test]
+fn does_fail_with_catch_panic_status() {
+    let mut error_msg = ptr::null();
+    unsafe {
+        let status = fn_does_fail_with_catch_panic(&mut error_msg);
+        assert_eq!(status, FCPResponseStatus::FCPCallerError);
+        let error_message = CString::from_raw(error_msg as *mut _)
+            .into_string()
+            .unwrap();
+        assert_eq!(error_message, "I do fail");
+    }
+}
+
+#[test]
+fn does_panic_with_catch_panic_status() {
+    let mut error_msg = ptr::null();
+    unsafe {
+        let status = fn_does_panic_with_catch_panic(&mut error_msg);
+        assert_eq!(status, FCPResponseStatus::FCPUnclassifiedError);
+        let error_message = CString::from_raw(error_msg as *mut _)
+            .into_string()
+            .unwrap();
+        assert_eq!(error_message, "Rust panic: I do panic");
+
+        // A NULL out-pointer is fine, the message is just not reported
+        let status = fn_does_panic_with_catch_panic(ptr::null_mut());
+        assert_eq!(status, FCPResponseStatus::FCPUnclassifiedError);
+    }
+}
@@ -0,0 +1,29 @@
+// Not every test uses every part of the shared fixtures
+#![allow(dead_code)]
+
+use std::ptr;
+
+use drop_struct_macro_derive::DropStructMacro;
+// `free_c_str` is needed by `DropStructMacro`
+// `CodeAndMessage` is the trait implemented by `code_and_message_impl`
+use ffi_toolkit::{code_and_message_impl, free_c_str, CodeAndMessage, FCPResponseStatus};
+
+#[repr(C)]
+#[derive(DropStructMacro)]
+pub struct BasicResponse {
+    pub status_code: FCPResponseStatus,
+    pub error_msg: *const libc::c_char,
+    pub is_valid: bool,
+}
+
+impl Default for BasicResponse {
+    fn default() -> Self {
+        BasicResponse {
+            status_code: FCPResponseStatus::FCPNoError,
+            error_msg: ptr::null(),
+            is_valid: false,
+        }
+    }
+}
+
+code_and_message_impl!(BasicResponse);
@@ -0,0 +1,73 @@
+// Only a single test lives in this file as the crash dump directory is process-wide.
+use std::fs;
+use std::panic;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::ptr;
+
+use ffi_toolkit::{
+    c_str_to_rust_str, catch_panic_response, catch_panic_status, get_crash_dump_dir,
+    set_crash_dump_dir, FCPResponseStatus,
+};
+
+mod common;
+
+use common::BasicResponse;
+
+fn error_message(response: *mut BasicResponse) -> String {
+    // Dropping the response frees the error message
+    let response = unsafe { Box::from_raw(response) };
+    assert_eq!(
+        response.status_code,
+        FCPResponseStatus::FCPUnclassifiedError
+    );
+    unsafe { c_str_to_rust_str(response.error_msg) }.into_owned()
+}
+
+// the crash dump path from an error message with the given panic message
+fn dump_path(message: &str, panic_message: &str, dump_dir: &Path) -> PathBuf {
+    let prefix = format!("Rust panic: {} (crash dump: ", panic_message);
+    assert!(message.starts_with(&prefix), "{}", message);
+    let dump_path = PathBuf::from(&message[prefix.len()..message.len() - 1]);
+    assert!(dump_path.starts_with(dump_dir));
+    dump_path
+}
+
+#[test]
+fn writes_crash_dump_for_caught_panic() {
+    let dump_dir = std::env::temp_dir().join(format!("ffi-toolkit-crash-dump-{}", process::id()));
+    set_crash_dump_dir(Some(dump_dir.clone()));
+    assert_eq!(get_crash_dump_dir(), Some(dump_dir.clone()));
+
+    let message = error_message(catch_panic_response::<_, BasicResponse>(|| {
+        panic!("I do panic")
+    }));
+    let dump = fs::read_to_string(dump_path(&message, "I do panic", &dump_dir)).unwrap();
+    assert!(dump.contains("message: I do panic"));
+    assert!(dump.contains("thread: writes_crash_dump_for_caught_panic"));
+    assert!(!dump.contains("<not captured>"));
+
+    // A payload passed on with `resume_unwind()` doesn't run the panic hook, hence it must not
+    // pick up the backtrace of an unrelated panic that was caught elsewhere before
+    let _ = panic::catch_unwind(|| panic!("caught elsewhere"));
+    let message = error_message(catch_panic_response::<_, BasicResponse>(|| {
+        panic::resume_unwind(Box::new("resumed"))
+    }));
+    let dump = fs::read_to_string(dump_path(&message, "resumed", &dump_dir)).unwrap();
+    assert!(dump.contains("<not captured>"));
+
+    // A dump is also written if the caller doesn't want the error message
+    let num_dumps = fs::read_dir(&dump_dir).unwrap().count();
+    let status = unsafe { catch_panic_status(ptr::null_mut(), || panic!("I do panic")) };
+    assert_eq!(status, FCPResponseStatus::FCPUnclassifiedError);
+    assert_eq!(fs::read_dir(&dump_dir).unwrap().count(), num_dumps + 1);
+
+    // Without a crash dump directory the plain message is returned
+    set_crash_dump_dir(None);
+    let message = error_message(catch_panic_response::<_, BasicResponse>(|| {
+        panic!("I do panic")
+    }));
+    assert_eq!(message, "Rust panic: I do panic");
+
+    fs::remove_dir_all(&dump_dir).unwrap();
+}
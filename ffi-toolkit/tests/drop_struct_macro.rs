@@ -0,0 +1,64 @@
+// Checks that the `Drop` implementation generated by `DropStructMacro` frees exactly what was
+// allocated. Run under LeakSanitizer (see the `test_sanitize` CI job) this also catches leaks that
+// the counters below can't see. Only a single test lives in this file as the counts are
+// process-wide.
+use std::ptr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use drop_struct_macro_derive::DropStructMacro;
+// `free_c_str` is needed by `DropStructMacro`
+use ffi_toolkit::{
+    free_c_str, free_raw_ptr, get_live_ffi_object_counts, raw_ptr_tracked, rust_str_to_c_str,
+};
+
+static ELEMENTS_DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+#[repr(C)]
+pub struct Element {
+    pub value: u64,
+}
+
+impl Drop for Element {
+    fn drop(&mut self) {
+        ELEMENTS_DROPPED.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[repr(C)]
+#[derive(DropStructMacro)]
+pub struct ReadResponse {
+    pub error_msg: *const libc::c_char,
+    pub path: *const libc::c_char,
+    pub elements_len: libc::size_t,
+    pub elements_ptr: *const Element,
+}
+
+// a pointer/length pair whose capacity is guaranteed to equal its length, as the generated `Drop`
+// implementation expects
+fn into_raw_parts(elements: Vec<Element>) -> (*const Element, libc::size_t) {
+    let len = elements.len();
+    let ptr = Box::into_raw(elements.into_boxed_slice()) as *const Element;
+    (ptr, len)
+}
+
+#[test]
+fn drop_frees_c_strings_and_arrays() {
+    let (elements_ptr, elements_len) =
+        into_raw_parts((0..3).map(|value| Element { value }).collect());
+    let response = raw_ptr_tracked(ReadResponse {
+        error_msg: ptr::null(),
+        path: rust_str_to_c_str("/var/tmp/sealed"),
+        elements_len,
+        elements_ptr,
+    });
+
+    unsafe {
+        assert_eq!((*(*response).elements_ptr.add(2)).value, 2);
+        free_raw_ptr(response);
+    }
+    assert_eq!(ELEMENTS_DROPPED.load(Ordering::SeqCst), 3);
+    // Both the response and its C string were freed
+    if cfg!(debug_assertions) {
+        assert!(get_live_ffi_object_counts().is_empty());
+    }
+}
@@ -0,0 +1,89 @@
+use std::fs::File;
+use std::io;
+
+use ffi_toolkit::{
+    c_str_to_rust_str, io_error_code_and_message, io_error_kind_status, io_error_status,
+    CodeAndMessage, FCPResponseStatus,
+};
+
+mod common;
+
+use common::BasicResponse;
+
+#[test]
+fn caller_errors() {
+    for kind in &[
+        io::ErrorKind::NotFound,
+        io::ErrorKind::PermissionDenied,
+        io::ErrorKind::NotADirectory,
+        io::ErrorKind::IsADirectory,
+        io::ErrorKind::InvalidFilename,
+        io::ErrorKind::AlreadyExists,
+        io::ErrorKind::InvalidInput,
+    ] {
+        assert_eq!(
+            io_error_kind_status(*kind),
+            FCPResponseStatus::FCPCallerError,
+            "{:?}",
+            kind
+        );
+    }
+}
+
+#[test]
+fn receiver_errors() {
+    for kind in &[
+        io::ErrorKind::InvalidData,
+        io::ErrorKind::UnexpectedEof,
+        io::ErrorKind::WriteZero,
+        io::ErrorKind::Interrupted,
+        io::ErrorKind::WouldBlock,
+        io::ErrorKind::TimedOut,
+        io::ErrorKind::BrokenPipe,
+        io::ErrorKind::Other,
+    ] {
+        assert_eq!(
+            io_error_kind_status(*kind),
+            FCPResponseStatus::FCPReceiverError,
+            "{:?}",
+            kind
+        );
+    }
+}
+
+/// Opening a file that doesn't exist is classified the same way no matter where it happens.
+#[test]
+fn open_missing_file_is_caller_error() {
+    let err = File::open("/this/path/does/not/exist").unwrap_err();
+    assert_eq!(io_error_status(&err), FCPResponseStatus::FCPCallerError);
+
+    // Dropping the response frees the error message
+    let mut response = BasicResponse::default();
+    response.set_error(io_error_code_and_message(&err));
+    assert_eq!(response.status_code, FCPResponseStatus::FCPCallerError);
+    let error_message = unsafe { c_str_to_rust_str(response.error_msg) };
+    assert_eq!(error_message, format!("{}", err));
+}
+
+/// Custom error messages may contain NUL bytes, they are escaped instead of aborting.
+#[test]
+fn error_message_with_nul() {
+    let err = io::Error::other("bad\0key");
+    let mut response = BasicResponse::default();
+    response.set_error(io_error_code_and_message(&err));
+    assert_eq!(response.status_code, FCPResponseStatus::FCPReceiverError);
+    let error_message = unsafe { c_str_to_rust_str(response.error_msg) };
+    assert_eq!(error_message, "bad\\0key");
+}
+
+/// A path that has a file as one of its directory components is the caller's fault.
+#[cfg(unix)]
+#[test]
+fn path_through_file_is_caller_error() {
+    let file = std::env::temp_dir().join(format!("ffi-toolkit-io-error-{}", std::process::id()));
+    File::create(&file).unwrap();
+    let err = File::open(file.join("sealed")).unwrap_err();
+    std::fs::remove_file(&file).unwrap();
+    assert_eq!(err.kind(), io::ErrorKind::NotADirectory);
+    assert_eq!(io_error_status(&err), FCPResponseStatus::FCPCallerError);
+}
@@ -0,0 +1,88 @@
+// Only a single test lives in this file as the counts are process-wide.
+#![cfg(debug_assertions)]
+
+use std::any;
+use std::ffi::CString;
+use std::ptr;
+use std::slice;
+
+use drop_struct_macro_derive::DropStructMacro;
+use ffi_toolkit::{
+    c_str_to_rust_str, catch_panic_response, ffi_toolkit_destroy_live_object_counts_response,
+    ffi_toolkit_get_live_object_counts, free_c_str, free_raw_ptr, get_live_ffi_object_counts,
+    raw_ptr, raw_ptr_tracked, rust_str_to_c_str, C_STR_TYPE_NAME,
+};
+
+mod common;
+use common::BasicResponse;
+
+#[repr(C)]
+#[derive(DropStructMacro)]
+pub struct MessageResponse {
+    pub message: *const libc::c_char,
+}
+
+#[test]
+fn counts_live_objects_until_freed() {
+    let response_type = any::type_name::<MessageResponse>();
+    assert!(get_live_ffi_object_counts().is_empty());
+
+    let first = raw_ptr_tracked(MessageResponse {
+        message: rust_str_to_c_str("first"),
+    });
+    let second = raw_ptr_tracked(MessageResponse {
+        message: ptr::null(),
+    });
+    // Objects from `raw_ptr` are not tracked
+    let untracked = raw_ptr(MessageResponse {
+        message: ptr::null(),
+    });
+    let counts = get_live_ffi_object_counts();
+    assert_eq!(counts.get(response_type), Some(&2));
+    assert_eq!(counts.get(C_STR_TYPE_NAME), Some(&1));
+
+    // The same counts are available over FFI, querying them doesn't change them
+    let ffi_counts = ffi_toolkit_get_live_object_counts();
+    let mut entries = unsafe {
+        slice::from_raw_parts((*ffi_counts).counts_ptr, (*ffi_counts).counts_len)
+            .iter()
+            .map(|entry| (c_str_to_rust_str(entry.type_name).into_owned(), entry.count))
+            .collect::<Vec<_>>()
+    };
+    unsafe { ffi_toolkit_destroy_live_object_counts_response(ffi_counts) };
+    entries.sort();
+    let mut expected = vec![
+        (response_type.to_string(), 2),
+        (C_STR_TYPE_NAME.to_string(), 1),
+    ];
+    expected.sort();
+    assert_eq!(entries, expected);
+    assert_eq!(get_live_ffi_object_counts(), counts);
+
+    // Error responses created on panic are tracked as well
+    let panicked = catch_panic_response(|| -> *mut BasicResponse { panic!("tracked") });
+    let counts = get_live_ffi_object_counts();
+    assert_eq!(counts.get(any::type_name::<BasicResponse>()), Some(&1));
+    assert_eq!(counts.get(C_STR_TYPE_NAME), Some(&2));
+    unsafe { free_raw_ptr(panicked) };
+
+    // Dropping the response frees its message through `free_c_str`
+    unsafe { free_raw_ptr(first) };
+    let counts = get_live_ffi_object_counts();
+    assert_eq!(counts.get(response_type), Some(&1));
+    assert_eq!(counts.get(C_STR_TYPE_NAME), None);
+
+    // Freeing strings that were not created by `rust_str_to_c_str` doesn't hide tracked ones
+    let leaked = rust_str_to_c_str("leaked");
+    unsafe { free_c_str(CString::new("foreign").unwrap().into_raw()) };
+    assert_eq!(get_live_ffi_object_counts().get(C_STR_TYPE_NAME), Some(&1));
+    unsafe { free_c_str(leaked) };
+
+    unsafe {
+        free_raw_ptr(untracked);
+        free_raw_ptr(second);
+        free_raw_ptr(ptr::null_mut::<MessageResponse>());
+        free_c_str(ptr::null_mut());
+    }
+    assert!(get_live_ffi_object_counts().is_empty());
+}